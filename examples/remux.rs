@@ -16,17 +16,16 @@ async fn main() {
                 .option(Parameter::KeyValue("crf", "28")),
         );
 
-    let ffmpeg = builder.run().await.unwrap();
+    let mut ffmpeg = builder.run().await.unwrap();
 
-    ffmpeg
-        .progress
+    (&mut ffmpeg.progress)
         .for_each(|x| {
             dbg!(x.unwrap());
             ready(())
         })
         .await;
 
-    let output = ffmpeg.process.wait_with_output().unwrap();
+    let output = ffmpeg.wait().await.unwrap();
 
     println!(
         "{}\nstderr:\n{}",
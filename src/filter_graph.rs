@@ -0,0 +1,262 @@
+//! A typed builder for `-filter_complex` graphs, so filter chains don't have to be hand-written
+//! as raw strings.
+
+use std::time::Duration;
+
+use crate::{File, Parameter};
+
+/// A single node in a [FilterGraph]: an input label set, a filter with keyed args, and an
+/// output label set.
+///
+/// Serializes to ffmpeg's `[in0][in1]filter=k=v:k2=v2[out]` syntax.
+#[derive(Debug, Clone)]
+pub struct FilterNode {
+    inputs: Vec<String>,
+    filter: String,
+    args: Vec<(String, String)>,
+    outputs: Vec<String>,
+}
+
+impl FilterNode {
+    /// Starts a node using the given filter, ex. `concat` or `xfade`.
+    pub fn new(filter: impl Into<String>) -> Self {
+        FilterNode {
+            inputs: Vec::new(),
+            filter: filter.into(),
+            args: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Adds an input pad label, ex. `0:v`.
+    pub fn input(mut self, label: impl Into<String>) -> Self {
+        self.inputs.push(label.into());
+
+        self
+    }
+
+    /// Adds an output pad label, ex. `v0`.
+    pub fn output(mut self, label: impl Into<String>) -> Self {
+        self.outputs.push(label.into());
+
+        self
+    }
+
+    /// Adds a keyed argument to the filter, ex. `arg("duration", "1.5")`.
+    pub fn arg(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.args.push((key.into(), value.into()));
+
+        self
+    }
+
+    fn to_filter_string(&self) -> String {
+        let inputs: String = self.inputs.iter().map(|l| format!("[{}]", l)).collect();
+        let outputs: String = self.outputs.iter().map(|l| format!("[{}]", l)).collect();
+
+        if self.args.is_empty() {
+            format!("{}{}{}", inputs, self.filter, outputs)
+        } else {
+            let args = self
+                .args
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(":");
+
+            format!("{}{}={}{}", inputs, self.filter, args, outputs)
+        }
+    }
+}
+
+/// A typed `-filter_complex` graph: a sequence of labeled [FilterNode]s, plus the subset of
+/// their output labels that should be `-map`'d onto an output file.
+#[derive(Debug, Clone, Default)]
+pub struct FilterGraph {
+    filter_complex: String,
+    outputs: Vec<String>,
+}
+
+impl FilterGraph {
+    /// Gets a [FilterGraph] with no nodes.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends a node to the graph.
+    pub fn node(mut self, node: FilterNode) -> Self {
+        if !self.filter_complex.is_empty() {
+            self.filter_complex.push(';');
+        }
+        self.filter_complex.push_str(&node.to_filter_string());
+
+        self
+    }
+
+    /// Marks `label` as one of the graph's final outputs, to be `-map`'d onto an output file.
+    pub fn map(mut self, label: impl Into<String>) -> Self {
+        self.outputs.push(format!("[{}]", label.into()));
+
+        self
+    }
+
+    /// Builds a graph that concatenates `segments` video+audio pairs, referenced by ffmpeg
+    /// input index (`0:v`/`0:a`, `1:v`/`1:a`, ...), into a single `outv`/`outa` pair.
+    pub fn concat(segments: usize) -> Self {
+        let mut node = FilterNode::new("concat");
+        for i in 0..segments {
+            node = node.input(format!("{}:v", i)).input(format!("{}:a", i));
+        }
+        node = node
+            .arg("n", segments.to_string())
+            .arg("v", "1")
+            .arg("a", "1")
+            .output("outv")
+            .output("outa");
+
+        FilterGraph::new().node(node).map("outv").map("outa")
+    }
+
+    /// Builds a graph with a single `xfade` crossfade between video inputs `0:v` and `1:v`,
+    /// transitioning over `duration` starting at `offset` into the first clip.
+    pub fn xfade(duration: Duration, offset: Duration) -> Self {
+        Self::xfade_transition("fade", duration, offset)
+    }
+
+    /// Like [Self::xfade], but fades through black instead of crossfading directly.
+    pub fn fadeblack(duration: Duration, offset: Duration) -> Self {
+        Self::xfade_transition("fadeblack", duration, offset)
+    }
+
+    fn xfade_transition(transition: &str, duration: Duration, offset: Duration) -> Self {
+        let node = FilterNode::new("xfade")
+            .input("0:v")
+            .input("1:v")
+            .arg("transition", transition)
+            .arg("duration", format!("{:.3}", duration.as_secs_f64()))
+            .arg("offset", format!("{:.3}", offset.as_secs_f64()))
+            .output("v");
+
+        FilterGraph::new().node(node).map("v")
+    }
+
+    /// Expands this graph's `-filter_complex` option, plus `-map` options for its final
+    /// labeled outputs, onto an output [File].
+    ///
+    /// These are per-output options, not global ones: `-map` only makes sense once ffmpeg
+    /// knows which output it's mapping onto, so `attach` takes the output `File` itself rather
+    /// than the [`FfmpegBuilder`](crate::FfmpegBuilder), the same way
+    /// [`Profile::apply`](crate::Profile) expands onto a `File` too.
+    pub fn attach<'a>(&'a self, mut file: File<'a>) -> File<'a> {
+        file = file.option(Parameter::KeyValue("filter_complex", &self.filter_complex));
+
+        for output in &self.outputs {
+            file = file.option(Parameter::KeyValue("map", output));
+        }
+
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_filter_string_without_args() {
+        let node = FilterNode::new("concat").input("0:v").input("1:v").output("outv");
+
+        assert_eq!(node.to_filter_string(), "[0:v][1:v]concat[outv]");
+    }
+
+    #[test]
+    fn to_filter_string_with_args() {
+        let node = FilterNode::new("xfade")
+            .input("0:v")
+            .input("1:v")
+            .arg("transition", "fade")
+            .arg("duration", "1.000")
+            .output("v");
+
+        assert_eq!(
+            node.to_filter_string(),
+            "[0:v][1:v]xfade=transition=fade:duration=1.000[v]"
+        );
+    }
+
+    #[test]
+    fn to_filter_string_with_multiple_outputs() {
+        let node = FilterNode::new("concat")
+            .input("0:v")
+            .input("0:a")
+            .arg("n", "1")
+            .arg("v", "1")
+            .arg("a", "1")
+            .output("outv")
+            .output("outa");
+
+        assert_eq!(
+            node.to_filter_string(),
+            "[0:v][0:a]concat=n=1:v=1:a=1[outv][outa]"
+        );
+    }
+
+    #[test]
+    fn concat_builds_the_filter_complex_string() {
+        let graph = FilterGraph::concat(2);
+
+        assert_eq!(
+            graph.filter_complex,
+            "[0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[outv][outa]"
+        );
+        assert_eq!(graph.outputs, vec!["[outv]", "[outa]"]);
+    }
+
+    #[test]
+    fn xfade_builds_the_filter_complex_string() {
+        let graph = FilterGraph::xfade(Duration::from_millis(1500), Duration::from_millis(2000));
+
+        assert_eq!(
+            graph.filter_complex,
+            "[0:v][1:v]xfade=transition=fade:duration=1.500:offset=2.000[v]"
+        );
+        assert_eq!(graph.outputs, vec!["[v]"]);
+    }
+
+    #[test]
+    fn fadeblack_uses_the_fadeblack_transition() {
+        let graph = FilterGraph::fadeblack(Duration::from_secs(1), Duration::from_secs(3));
+
+        assert_eq!(
+            graph.filter_complex,
+            "[0:v][1:v]xfade=transition=fadeblack:duration=1.000:offset=3.000[v]"
+        );
+    }
+
+    fn key_values(file: &File) -> Vec<(&str, &str)> {
+        file.options
+            .iter()
+            .map(|option| match option {
+                Parameter::KeyValue(key, value) => (*key, *value),
+                Parameter::Single(key) => (*key, ""),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn attach_appends_bracketed_map_options_to_the_file() {
+        let graph = FilterGraph::concat(2);
+        let file = graph.attach(File::new("out.mp4"));
+
+        assert_eq!(
+            key_values(&file),
+            vec![
+                (
+                    "filter_complex",
+                    "[0:v][0:a][1:v][1:a]concat=n=2:v=1:a=1[outv][outa]"
+                ),
+                ("map", "[outv]"),
+                ("map", "[outa]"),
+            ]
+        );
+    }
+}
@@ -23,17 +23,16 @@
 //!                 .option(Parameter::KeyValue("crf", "28")),
 //!         );
 //!
-//!     let ffmpeg = builder.run().await.unwrap();
+//!     let mut ffmpeg = builder.run().await.unwrap();
 //!
-//!     ffmpeg
-//!         .progress
+//!     (&mut ffmpeg.progress)
 //!         .for_each(|x| {
 //!             dbg!(x.unwrap());
 //!             ready(())
 //!         })
 //!         .await;
 //!
-//!     let output = ffmpeg.process.wait_with_output().unwrap();
+//!     let output = ffmpeg.wait().await.unwrap();
 //!
 //!     println!(
 //!         "{}\nstderr:\n{}",
@@ -44,10 +43,24 @@
 //! ```
 #![warn(missing_docs)]
 
-use std::process::{Command, Stdio};
+use std::{
+    pin::Pin,
+    process::{Command, Stdio},
+};
 
+use tokio::io::{AsyncRead, AsyncWrite};
+
+mod filter_graph;
+mod probe;
+mod profile;
 mod runner;
 
+#[doc(inline)]
+pub use filter_graph::*;
+#[doc(inline)]
+pub use probe::*;
+#[doc(inline)]
+pub use profile::*;
 #[doc(inline)]
 pub use runner::*;
 
@@ -74,7 +87,6 @@ pub struct FfmpegBuilder<'a> {
 /// A file that ffmpeg operates on.
 ///
 /// This can be an input or output, it depends on what you add it as.
-#[derive(Debug)]
 pub struct File<'a> {
     /// The url of the file.
     ///
@@ -82,6 +94,27 @@ pub struct File<'a> {
     pub url: &'a str,
     /// The options corresponding to this file.
     pub options: Vec<Parameter<'a>>,
+    /// In-memory I/O to pipe through ffmpeg's `pipe:` protocol, instead of reading or writing
+    /// `url` directly. Set by [File::from_reader] and [File::to_writer].
+    pub(crate) io: Option<PipeIo>,
+}
+
+impl<'a> std::fmt::Debug for File<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("File")
+            .field("url", &self.url)
+            .field("options", &self.options)
+            .field("io", &self.io.is_some())
+            .finish()
+    }
+}
+
+/// In-memory I/O piped to or from ffmpeg in place of a path on disk.
+pub(crate) enum PipeIo {
+    /// Bytes copied into ffmpeg's stdin as an input.
+    Reader(Pin<Box<dyn AsyncRead + Send>>),
+    /// Bytes copied out of ffmpeg's stdout as an output.
+    Writer(Pin<Box<dyn AsyncWrite + Send>>),
 }
 
 /// A global or file option to be passed to ffmpeg.
@@ -186,6 +219,33 @@ impl<'a> File<'a> {
         File {
             url,
             options: Vec::new(),
+            io: None,
+        }
+    }
+
+    /// Gets an input file that reads from `reader` instead of a path on disk, using ffmpeg's
+    /// `pipe:` protocol.
+    ///
+    /// When this file is used as an input, [`run`](FfmpegBuilder::run) pipes `reader` into
+    /// ffmpeg's stdin instead of staging it to disk first.
+    pub fn from_reader(reader: impl AsyncRead + Send + 'static) -> File<'static> {
+        File {
+            url: "pipe:0",
+            options: Vec::new(),
+            io: Some(PipeIo::Reader(Box::pin(reader))),
+        }
+    }
+
+    /// Gets an output file that writes to `writer` instead of a path on disk, using ffmpeg's
+    /// `pipe:` protocol.
+    ///
+    /// When this file is used as an output, [`run`](FfmpegBuilder::run) drains ffmpeg's stdout
+    /// into `writer` instead of writing it to disk.
+    pub fn to_writer(writer: impl AsyncWrite + Send + 'static) -> File<'static> {
+        File {
+            url: "pipe:1",
+            options: Vec::new(),
+            io: Some(PipeIo::Writer(Box::pin(writer))),
         }
     }
 
@@ -0,0 +1,239 @@
+//! Queries media files with `ffprobe`, ffmpeg's sibling inspection tool.
+
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::process::Command;
+
+use crate::File;
+
+type Result<T> = std::result::Result<T, ProbeError>;
+
+/// Information about a media file, as reported by `ffprobe`.
+///
+/// Every field is an option because a file might not have the stream it
+/// describes (ex. `audio_codec` on a silent video), or because ffprobe
+/// couldn't determine it.
+#[derive(Debug, Default, Clone)]
+pub struct MediaInfo {
+    /// How long the file plays for.
+    pub duration: Option<Duration>,
+    /// The width of the first video stream, in pixels.
+    pub width: Option<u32>,
+    /// The height of the first video stream, in pixels.
+    pub height: Option<u32>,
+    /// The framerate of the first video stream.
+    pub fps: Option<f64>,
+    /// The name of the first video stream's codec, ex. `h264`.
+    pub codec: Option<String>,
+    /// The sample rate of the first audio stream, in Hz.
+    pub audio_sample_rate: Option<u32>,
+    /// The number of channels in the first audio stream.
+    pub audio_channels: Option<u32>,
+    /// The name of the first audio stream's codec, ex. `aac`.
+    pub audio_codec: Option<String>,
+}
+
+/// Builds up a call to `ffprobe`, for inspecting a [File] before transcoding it.
+#[derive(Debug)]
+pub struct FfprobeBuilder<'a> {
+    /// The command that's run for ffprobe. Usually just `ffprobe`.
+    pub ffprobe_command: &'a str,
+}
+
+impl<'a> FfprobeBuilder<'a> {
+    /// Gets a [FfprobeBuilder] with nothing set.
+    pub fn new() -> FfprobeBuilder<'a> {
+        FfprobeBuilder {
+            ffprobe_command: "ffprobe",
+        }
+    }
+
+    /// Sets the command that's run for ffprobe.
+    pub fn ffprobe_command(mut self, ffprobe_command: &'a str) -> Self {
+        self.ffprobe_command = ffprobe_command;
+
+        self
+    }
+
+    /// Runs ffprobe against `file` and parses the result into a [MediaInfo].
+    ///
+    /// `file` must be backed by a url, not in-memory I/O: ffprobe runs as its own process with
+    /// its own stdio, so a [`File::from_reader`](crate::File::from_reader) or
+    /// [`File::to_writer`](crate::File::to_writer) file can't be piped through it the way it can
+    /// through [`FfmpegBuilder::run`](crate::FfmpegBuilder::run).
+    pub async fn run(self, file: &File<'a>) -> Result<MediaInfo> {
+        if file.io.is_some() {
+            return Err(ProbeError::UnsupportedPipedFile);
+        }
+
+        let mut info = MediaInfo::default();
+
+        let format = self.query(file, "format=duration", None).await?;
+        if let Some(duration) = format.first().and_then(|s| parse_opt::<f64>(s)) {
+            info.duration = Some(Duration::from_secs_f64(duration));
+        }
+
+        let video = self
+            .query(file, "stream=width,height,r_frame_rate,codec_name", Some("v:0"))
+            .await?;
+        // ffprobe prints a stream's entries in its own fixed order (codec_name first, then the
+        // per-type fields, then r_frame_rate), not in the order they're listed in -show_entries.
+        if let [codec, width, height, fps] = &video[..] {
+            info.width = parse_opt(width);
+            info.height = parse_opt(height);
+            info.fps = parse_rational(fps);
+            info.codec = parse_str_opt(codec);
+        }
+
+        let audio = self
+            .query(file, "stream=sample_rate,channels,codec_name", Some("a:0"))
+            .await?;
+        if let [codec, sample_rate, channels] = &audio[..] {
+            info.audio_sample_rate = parse_opt(sample_rate);
+            info.audio_channels = parse_opt(channels);
+            info.audio_codec = parse_str_opt(codec);
+        }
+
+        Ok(info)
+    }
+
+    /// Runs ffprobe with the given `-show_entries` value, optionally restricted to a single
+    /// stream, and returns the unlabeled values it printed, one per line.
+    async fn query(
+        &self,
+        file: &File<'a>,
+        show_entries: &str,
+        select_stream: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let mut command = Command::new(self.ffprobe_command);
+        command
+            .arg("-v")
+            .arg("error")
+            .arg("-of")
+            .arg("default=noprint_wrappers=1:nokey=1")
+            .arg("-show_entries")
+            .arg(show_entries);
+
+        if let Some(select_stream) = select_stream {
+            command.arg("-select_streams").arg(select_stream);
+        }
+
+        command.arg(file.url);
+
+        let output = command.output().await?;
+        if !output.status.success() {
+            return Err(ProbeError::NonZeroExit(
+                output.status,
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
+
+impl<'a> Default for FfprobeBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_opt<T: std::str::FromStr>(s: &str) -> Option<T> {
+    if s == "N/A" {
+        None
+    } else {
+        s.parse().ok()
+    }
+}
+
+fn parse_str_opt(s: &str) -> Option<String> {
+    if s == "N/A" {
+        None
+    } else {
+        Some(s.to_owned())
+    }
+}
+
+/// Parses a framerate given as a rational, ex. `30000/1001`.
+fn parse_rational(s: &str) -> Option<f64> {
+    let (num, den) = s.split_once('/')?;
+    let num: f64 = num.parse().ok()?;
+    let den: f64 = den.parse().ok()?;
+
+    if den == 0.0 {
+        None
+    } else {
+        Some(num / den)
+    }
+}
+
+/// Errors that can occur while probing a file.
+#[derive(Error, Debug)]
+pub enum ProbeError {
+    /// Anything threw an [io::Error](std::io::Error).
+    #[error("Io Error: {0}")]
+    IoError(
+        #[source]
+        #[from]
+        std::io::Error,
+    ),
+    /// Ffprobe exited with a non-zero status.
+    #[error("ffprobe exited with status {0}: {1}")]
+    NonZeroExit(std::process::ExitStatus, String),
+    /// [`FfprobeBuilder::run`] was given a file backed by in-memory I/O instead of a url.
+    #[error("can't probe a file piped through in-memory I/O, only a file backed by a url")]
+    UnsupportedPipedFile,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_opt_parses_numbers() {
+        assert_eq!(parse_opt::<u32>("1920"), Some(1920));
+        assert_eq!(parse_opt::<f64>("29.97"), Some(29.97));
+    }
+
+    #[test]
+    fn parse_opt_treats_na_as_none() {
+        assert_eq!(parse_opt::<u32>("N/A"), None);
+    }
+
+    #[test]
+    fn parse_opt_treats_unparseable_as_none() {
+        assert_eq!(parse_opt::<u32>("not a number"), None);
+    }
+
+    #[test]
+    fn parse_str_opt_treats_na_as_none() {
+        assert_eq!(parse_str_opt("N/A"), None);
+    }
+
+    #[test]
+    fn parse_str_opt_passes_through_otherwise() {
+        assert_eq!(parse_str_opt("h264"), Some("h264".to_owned()));
+    }
+
+    #[test]
+    fn parse_rational_divides_num_by_den() {
+        assert_eq!(parse_rational("30000/1001"), Some(30000.0 / 1001.0));
+        assert_eq!(parse_rational("25/1"), Some(25.0));
+    }
+
+    #[test]
+    fn parse_rational_rejects_zero_denominator() {
+        assert_eq!(parse_rational("30/0"), None);
+    }
+
+    #[test]
+    fn parse_rational_rejects_malformed_input() {
+        assert_eq!(parse_rational("not-a-rational"), None);
+        assert_eq!(parse_rational("30000"), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Reusable transcode-profile presets, for declaring an encoder pipeline declaratively instead
+//! of assembling [Parameter]s by hand.
+
+use crate::{FfmpegBuilder, File, Parameter};
+
+/// How the video encoder should control output quality/size.
+#[derive(Debug, Clone)]
+pub enum RateControl {
+    /// A constant rate factor: lower is higher quality and a larger file.
+    Crf(String),
+    /// A target video bitrate, in kbps.
+    Bitrate(String),
+}
+
+impl RateControl {
+    /// Constant rate factor rate control, ex. `RateControl::crf(23)`.
+    pub fn crf(value: u32) -> Self {
+        RateControl::Crf(value.to_string())
+    }
+
+    /// Target-bitrate rate control, in kbps, ex. `RateControl::bitrate_kbps(3000)`.
+    pub fn bitrate_kbps(value: u32) -> Self {
+        RateControl::Bitrate(format!("{}k", value))
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            RateControl::Crf(_) => "crf",
+            RateControl::Bitrate(_) => "b:v",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            RateControl::Crf(v) | RateControl::Bitrate(v) => v,
+        }
+    }
+}
+
+/// An encoding target: video/audio codecs, rate control, resolution, pixel format, and
+/// container, bundled up so they can be applied to an output [File] in one call.
+///
+/// Build one with [Profile::new] and its setters, or start from a preset like
+/// [Profile::h264_web] and override fields from there.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    video_codec: Option<String>,
+    rate_control: Option<RateControl>,
+    scale: Option<String>,
+    pixel_format: Option<String>,
+    audio_codec: Option<String>,
+    audio_bitrate: Option<String>,
+    container: Option<String>,
+}
+
+impl Profile {
+    /// Gets a [Profile] with nothing set.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the video codec, ex. `libx264`.
+    pub fn video_codec(mut self, codec: impl Into<String>) -> Self {
+        self.video_codec = Some(codec.into());
+
+        self
+    }
+
+    /// Sets how the video encoder should control quality/size.
+    pub fn rate_control(mut self, rate_control: RateControl) -> Self {
+        self.rate_control = Some(rate_control);
+
+        self
+    }
+
+    /// Scales video to `width`x`height`.
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.scale = Some(format!("scale={}:{}", width, height));
+
+        self
+    }
+
+    /// Sets the output pixel format, ex. `yuv420p`.
+    pub fn pixel_format(mut self, format: impl Into<String>) -> Self {
+        self.pixel_format = Some(format.into());
+
+        self
+    }
+
+    /// Sets the audio codec, ex. `aac`.
+    pub fn audio_codec(mut self, codec: impl Into<String>) -> Self {
+        self.audio_codec = Some(codec.into());
+
+        self
+    }
+
+    /// Sets the audio bitrate, in kbps.
+    pub fn audio_bitrate(mut self, kbps: u32) -> Self {
+        self.audio_bitrate = Some(format!("{}k", kbps));
+
+        self
+    }
+
+    /// Sets the container format, passed to ffmpeg as `-f`.
+    pub fn container(mut self, container: impl Into<String>) -> Self {
+        self.container = Some(container.into());
+
+        self
+    }
+
+    /// H.264 for web delivery: CRF 23, 8-bit 4:2:0, in an mp4 container.
+    pub fn h264_web() -> Self {
+        Profile::new()
+            .video_codec("libx264")
+            .rate_control(RateControl::crf(23))
+            .pixel_format("yuv420p")
+            .container("mp4")
+    }
+
+    /// H.265 for archival: a low CRF for near-lossless quality, 10-bit 4:2:0, in an mkv
+    /// container.
+    pub fn h265_archive() -> Self {
+        Profile::new()
+            .video_codec("libx265")
+            .rate_control(RateControl::crf(18))
+            .pixel_format("yuv420p10le")
+            .container("matroska")
+    }
+
+    /// AAC audio only, for extracting or transcoding a standalone audio track.
+    pub fn aac_audio() -> Self {
+        Profile::new()
+            .audio_codec("aac")
+            .audio_bitrate(192)
+            .container("adts")
+    }
+
+    /// Expands this profile into the options it implies on an output [File].
+    fn apply<'a>(&'a self, mut file: File<'a>) -> File<'a> {
+        if let Some(codec) = &self.video_codec {
+            file = file.option(Parameter::KeyValue("vcodec", codec));
+        }
+        if let Some(rate_control) = &self.rate_control {
+            file = file.option(Parameter::KeyValue(rate_control.key(), rate_control.value()));
+        }
+        if let Some(scale) = &self.scale {
+            file = file.option(Parameter::KeyValue("vf", scale));
+        }
+        if let Some(pixel_format) = &self.pixel_format {
+            file = file.option(Parameter::KeyValue("pix_fmt", pixel_format));
+        }
+        if let Some(codec) = &self.audio_codec {
+            file = file.option(Parameter::KeyValue("acodec", codec));
+        }
+        if let Some(bitrate) = &self.audio_bitrate {
+            file = file.option(Parameter::KeyValue("b:a", bitrate));
+        }
+        if let Some(container) = &self.container {
+            file = file.option(Parameter::KeyValue("f", container));
+        }
+
+        file
+    }
+}
+
+impl<'a> FfmpegBuilder<'a> {
+    /// Adds an output file at `url`, with its encoding options populated from `profile`.
+    pub fn output_profiled(self, url: &'a str, profile: &'a Profile) -> Self {
+        let file = profile.apply(File::new(url));
+
+        self.output(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_values(file: &File) -> Vec<(&str, &str)> {
+        file.options
+            .iter()
+            .map(|option| match option {
+                Parameter::KeyValue(key, value) => (*key, *value),
+                Parameter::Single(key) => (*key, ""),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn h264_web_applies_options_in_order() {
+        let file = Profile::h264_web().apply(File::new("out.mp4"));
+
+        assert_eq!(
+            key_values(&file),
+            vec![
+                ("vcodec", "libx264"),
+                ("crf", "23"),
+                ("pix_fmt", "yuv420p"),
+                ("f", "mp4"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rate_control_bitrate_emits_b_v_not_crf() {
+        let file = Profile::new()
+            .rate_control(RateControl::bitrate_kbps(3000))
+            .apply(File::new("out.mp4"));
+
+        assert_eq!(key_values(&file), vec![("b:v", "3000k")]);
+    }
+
+    #[test]
+    fn apply_only_sets_options_that_were_configured() {
+        let file = Profile::new()
+            .video_codec("libx265")
+            .apply(File::new("out.mkv"));
+
+        assert_eq!(key_values(&file), vec![("vcodec", "libx265")]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{process::Child, time::Duration};
+use std::{process::Stdio, time::Duration};
 
 use futures::{
     channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
@@ -6,11 +6,12 @@ use futures::{
 };
 use thiserror::Error;
 use tokio::{
-    io::{AsyncBufReadExt, BufReader},
+    io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::TcpListener,
+    process::{Child, Command as TokioCommand},
 };
 
-use crate::{FfmpegBuilder, Parameter};
+use crate::{FfmpegBuilder, Parameter, PipeIo};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -20,7 +21,11 @@ pub struct Ffmpeg {
     /// The stream of progress events emitted by ffmpeg.
     pub progress: UnboundedReceiver<Result<Progress>>,
     /// The actual ffmpeg process.
-    pub process: Child,
+    ///
+    /// Wrapped in an option (rather than public) so [Self::wait] and [Self::cancel] can take it
+    /// out by value without a partial move out of `Self`, which isn't allowed now that `Ffmpeg`
+    /// implements [Drop]. Use [Self::wait] or [Self::cancel] instead of reaching in directly.
+    process: Option<Child>,
 }
 
 /// A progress event emitted by ffmpeg.
@@ -45,6 +50,16 @@ pub struct Progress {
     pub drop_frames: Option<u64>,
     /// How fast it is processing, relative to 1x playback speed.
     pub speed: Option<f64>,
+    /// How far through the input ffmpeg is, from `0.0` to `1.0`.
+    ///
+    /// Only available if a total duration was given to [FfmpegBuilder::run_with_duration].
+    /// Clamped to `[0.0, 1.0]`, since `out_time` can briefly exceed the probed duration on the
+    /// final packet.
+    pub fraction: Option<f64>,
+    /// An estimate of how much longer the encode will take, based on `speed`.
+    ///
+    /// Only available if a total duration was given to [FfmpegBuilder::run_with_duration].
+    pub eta: Option<Duration>,
     /// What ffmpeg will do now.
     pub status: Status,
 }
@@ -90,20 +105,94 @@ pub enum Error {
     /// The String is what it was trying to parse.
     #[error("Parse Error: {0}")]
     OtherParseError(#[source] Box<dyn std::error::Error + Send>, String),
+    /// Ffmpeg exited non-zero because it couldn't read or decode its input.
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    /// Ffmpeg exited non-zero because an encoder failed partway through.
+    #[error("Encoder error: {0}")]
+    EncoderError(String),
+    /// Ffmpeg exited non-zero for some other reason.
+    #[error("ffmpeg exited with code {code:?}: {stderr}")]
+    NonZeroExit {
+        /// The process's exit code, or [None] if it was killed by a signal.
+        code: Option<i32>,
+        /// The tail of ffmpeg's stderr output.
+        stderr: String,
+    },
+    /// More than one input (or output) file was set up with
+    /// [`File::from_reader`](crate::File::from_reader) (or
+    /// [`File::to_writer`](crate::File::to_writer)).
+    ///
+    /// Ffmpeg's child process only has one stdin and one stdout to pipe through, so only one
+    /// piped input and one piped output can be wired up per run.
+    #[error("only one piped input and one piped output are supported per run")]
+    MultiplePipedFiles,
 }
 
+/// How many trailing lines of stderr to keep when classifying a failed run.
+const STDERR_TAIL_LINES: usize = 20;
+
 impl<'a> FfmpegBuilder<'a> {
     /// Spawns a new ffmpeg process and records the output, consuming the builder
     ///
     /// This has to consume the builder for stdin, etc to work
-    pub async fn run(mut self) -> Result<Ffmpeg> {
+    pub async fn run(self) -> Result<Ffmpeg> {
+        self.run_impl(None).await
+    }
+
+    /// Like [Self::run], but also computes [Progress::fraction] and [Progress::eta] against
+    /// `total`, the full duration of the input (as obtained from, for example,
+    /// [FfprobeBuilder::run](crate::FfprobeBuilder::run)).
+    pub async fn run_with_duration(self, total: Duration) -> Result<Ffmpeg> {
+        self.run_impl(Some(total)).await
+    }
+
+    async fn run_impl(mut self, total: Option<Duration>) -> Result<Ffmpeg> {
         let listener = TcpListener::bind("127.0.0.1:0").await?;
         let port = listener.local_addr()?.port();
         let prog_url = format!("tcp://127.0.0.1:{}", port);
 
+        let mut piped_input = None;
+        for file in self.inputs.iter_mut() {
+            if let Some(PipeIo::Reader(reader)) = file.io.take() {
+                if piped_input.replace(reader).is_some() {
+                    return Err(Error::MultiplePipedFiles);
+                }
+            }
+        }
+
+        let mut piped_output = None;
+        for file in self.outputs.iter_mut() {
+            if let Some(PipeIo::Writer(writer)) = file.io.take() {
+                if piped_output.replace(writer).is_some() {
+                    return Err(Error::MultiplePipedFiles);
+                }
+            }
+        }
+
+        if piped_input.is_some() {
+            self.stdin = Stdio::piped();
+        }
+        if piped_output.is_some() {
+            self.stdout = Stdio::piped();
+        }
+
         self = self.option(Parameter::KeyValue("progress", &prog_url));
-        let mut command = self.to_command();
-        let child = command.spawn()?;
+        let command = self.to_command();
+        let mut child = TokioCommand::from(command).spawn()?;
+
+        if let Some(mut reader) = piped_input {
+            let mut stdin = child.stdin.take().expect("stdin requested as piped");
+            tokio::spawn(async move {
+                let _ = io::copy(&mut reader, &mut stdin).await;
+            });
+        }
+        if let Some(mut writer) = piped_output {
+            let mut stdout = child.stdout.take().expect("stdout requested as piped");
+            tokio::spawn(async move {
+                let _ = io::copy(&mut stdout, &mut writer).await;
+            });
+        }
 
         let conn = listener.accept().await?.0;
 
@@ -200,6 +289,14 @@ impl<'a> FfmpegBuilder<'a> {
                                     Status::End
                                 }
                             };
+
+                            if let Some(total) = total {
+                                let (fraction, eta) =
+                                    fraction_and_eta(total, progress.out_time, progress.speed);
+                                progress.fraction = fraction;
+                                progress.eta = eta;
+                            }
+
                             match tx.feed(Ok(progress)).await {
                                 Ok(_) => {}
                                 Err(e) => {
@@ -221,11 +318,121 @@ impl<'a> FfmpegBuilder<'a> {
 
         Ok(Ffmpeg {
             progress: rx,
-            process: child,
+            process: Some(child),
         })
     }
 }
 
+impl Ffmpeg {
+    /// Waits for ffmpeg to exit, treating a non-zero exit code as an error.
+    ///
+    /// Unlike inspecting [`process.wait_with_output()`](std::process::Child::wait_with_output)
+    /// directly, this classifies common failure causes by scanning the tail of ffmpeg's stderr,
+    /// so callers can decide whether to retry (ex. a transient encoder error) or reject (ex.
+    /// invalid input) without parsing stderr themselves. Classification only works if stderr
+    /// was piped, ex. via [`FfmpegBuilder::stderr`](crate::FfmpegBuilder::stderr).
+    pub async fn wait(mut self) -> Result<std::process::Output> {
+        let process = self
+            .process
+            .take()
+            .expect("Ffmpeg::wait or Ffmpeg::cancel already consumed the process");
+        let output = process.wait_with_output().await?;
+
+        if output.status.success() {
+            return Ok(output);
+        }
+
+        let tail = stderr_tail(&output.stderr);
+        Err(classify_failure(output.status.code(), tail))
+    }
+
+    /// Requests a clean shutdown of ffmpeg, then waits for it to exit.
+    ///
+    /// If ffmpeg's stdin was piped via [`FfmpegBuilder::stdin`](crate::FfmpegBuilder::stdin),
+    /// this writes `q\n` to it, triggering ffmpeg's own "press q to stop" handling so the output
+    /// container gets finalized properly. Otherwise, there's no clean way to ask ffmpeg to stop,
+    /// so this falls back to killing the process outright. That includes a run with a
+    /// [`File::from_reader`](crate::File::from_reader) input: its stdin handle is handed to the
+    /// task copying the reader in, so `cancel` kills the process in that case too.
+    pub async fn cancel(mut self) -> Result<()> {
+        let mut process = self
+            .process
+            .take()
+            .expect("Ffmpeg::wait or Ffmpeg::cancel already consumed the process");
+
+        match process.stdin.take() {
+            Some(mut stdin) => {
+                let _ = stdin.write_all(b"q\n").await;
+                let _ = stdin.flush().await;
+            }
+            None => process.start_kill()?,
+        }
+
+        process.wait().await?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Ffmpeg {
+    fn drop(&mut self) {
+        // Ignore the result: the process may have already exited, or already been taken by
+        // `wait`/`cancel`, either of which is fine.
+        if let Some(process) = &mut self.process {
+            let _ = process.start_kill();
+        }
+    }
+}
+
+/// Keeps the last [STDERR_TAIL_LINES] lines of `stderr`, in their original order.
+fn stderr_tail(stderr: &[u8]) -> String {
+    String::from_utf8_lossy(stderr)
+        .lines()
+        .rev()
+        .take(STDERR_TAIL_LINES)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Classifies a non-zero ffmpeg exit by scanning `tail` for known failure substrings.
+fn classify_failure(code: Option<i32>, tail: String) -> Error {
+    if tail.contains("Invalid data found") || tail.contains("No such file or directory") {
+        return Error::InvalidInput(tail);
+    }
+    if tail.contains("Error while opening encoder") || tail.contains("Unknown encoder") {
+        return Error::EncoderError(tail);
+    }
+
+    Error::NonZeroExit { code, stderr: tail }
+}
+
+/// Computes [Progress::fraction] and [Progress::eta] against `total`, given the most recently
+/// reported `out_time`/`speed`. Returns `(None, None)` if either is missing, ex. before ffmpeg
+/// reports its first `speed`.
+fn fraction_and_eta(
+    total: Duration,
+    out_time: Option<Duration>,
+    speed: Option<f64>,
+) -> (Option<f64>, Option<Duration>) {
+    let (Some(out_time), Some(speed)) = (out_time, speed) else {
+        return (None, None);
+    };
+
+    let fraction = (out_time.as_secs_f64() / total.as_secs_f64()).clamp(0.0, 1.0);
+
+    let eta = if speed > 0.0 {
+        let remaining = total.saturating_sub(out_time);
+        Some(Duration::from_secs_f64(remaining.as_secs_f64() / speed))
+    } else {
+        None
+    };
+
+    (Some(fraction), eta)
+}
+
 fn parse_line<'a>(line: &'a str) -> Option<(&'a str, &'a str)> {
     let trimmed = line.trim();
     let mut iter = trimmed.splitn(2, '=');
@@ -251,3 +458,95 @@ async fn handle_parse_error(
         .await;
     tx.close_channel();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fraction_and_eta_missing_out_time_or_speed() {
+        let total = Duration::from_secs(100);
+
+        assert_eq!(fraction_and_eta(total, None, Some(1.0)), (None, None));
+        assert_eq!(
+            fraction_and_eta(total, Some(Duration::from_secs(50)), None),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn fraction_and_eta_halfway_at_normal_speed() {
+        let total = Duration::from_secs(100);
+        let (fraction, eta) = fraction_and_eta(total, Some(Duration::from_secs(50)), Some(1.0));
+
+        assert_eq!(fraction, Some(0.5));
+        assert_eq!(eta, Some(Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn fraction_and_eta_clamps_past_total() {
+        let total = Duration::from_secs(100);
+        let (fraction, eta) = fraction_and_eta(total, Some(Duration::from_secs(110)), Some(1.0));
+
+        assert_eq!(fraction, Some(1.0));
+        assert_eq!(eta, Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn fraction_and_eta_no_eta_at_zero_speed() {
+        let total = Duration::from_secs(100);
+        let (fraction, eta) = fraction_and_eta(total, Some(Duration::from_secs(50)), Some(0.0));
+
+        assert_eq!(fraction, Some(0.5));
+        assert_eq!(eta, None);
+    }
+
+    #[test]
+    fn stderr_tail_keeps_only_the_last_lines() {
+        let stderr = (1..=30)
+            .map(|n| format!("line {}", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let tail = stderr_tail(stderr.as_bytes());
+
+        assert_eq!(tail.lines().count(), STDERR_TAIL_LINES);
+        assert!(tail.starts_with("line 11"));
+        assert!(tail.ends_with("line 30"));
+    }
+
+    #[test]
+    fn classify_failure_detects_invalid_input() {
+        let err = classify_failure(Some(1), "Invalid data found when processing input".into());
+
+        assert!(matches!(err, Error::InvalidInput(_)));
+    }
+
+    #[test]
+    fn classify_failure_detects_missing_file() {
+        let err = classify_failure(Some(1), "No such file or directory".into());
+
+        assert!(matches!(err, Error::InvalidInput(_)));
+    }
+
+    #[test]
+    fn classify_failure_detects_encoder_error() {
+        let err = classify_failure(Some(1), "Error while opening encoder for output stream".into());
+
+        assert!(matches!(err, Error::EncoderError(_)));
+    }
+
+    #[test]
+    fn classify_failure_detects_unknown_encoder() {
+        let err = classify_failure(Some(1), "Unknown encoder 'libx266'".into());
+
+        assert!(matches!(err, Error::EncoderError(_)));
+    }
+
+    #[test]
+    fn classify_failure_falls_back_to_non_zero_exit() {
+        let err = classify_failure(Some(1), "some other ffmpeg failure".into());
+
+        assert!(matches!(err, Error::NonZeroExit { code: Some(1), .. }));
+    }
+}